@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use kube::core::DynamicObject;
+use kube::runtime::reflector::Store;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Tracks readiness across every watched GVK: `/readyz` only reports ready
+/// once each one has delivered its first `Restarted` (initial list) event.
+#[derive(Clone)]
+pub struct Readiness {
+    seen: Arc<Mutex<HashSet<String>>>,
+    total: usize,
+}
+
+impl Readiness {
+    pub fn new(total: usize) -> Self {
+        Readiness {
+            seen: Arc::new(Mutex::new(HashSet::new())),
+            total,
+        }
+    }
+
+    pub fn mark_ready(&self, gvk_key: &str) {
+        self.seen.lock().unwrap().insert(gvk_key.to_string());
+    }
+
+    fn is_ready(&self) -> bool {
+        self.seen.lock().unwrap().len() >= self.total
+    }
+}
+
+#[derive(Clone)]
+struct AdminState {
+    stores: Arc<HashMap<String, Store<DynamicObject>>>,
+    readiness: Readiness,
+}
+
+#[derive(Serialize)]
+struct ObjectSummary {
+    gvk: String,
+    namespace: Option<String>,
+    name: String,
+    resource_version: Option<String>,
+}
+
+/// Serves the read-only admin API over `stores` (keyed by `resource::gvk_key`)
+/// at `addr` until the process exits.
+pub async fn serve(
+    addr: SocketAddr,
+    stores: HashMap<String, Store<DynamicObject>>,
+    readiness: Readiness,
+) -> Result<()> {
+    let state = AdminState {
+        stores: Arc::new(stores),
+        readiness,
+    };
+    let app = Router::new()
+        .route("/objects", get(list_objects))
+        .route("/objects/:gvk/:namespace/:name", get(get_namespaced_object))
+        .route("/objects/:gvk/:name", get(get_cluster_scoped_object))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind admin API on {addr}"))?;
+    axum::serve(listener, app)
+        .await
+        .context("admin API server failed")
+}
+
+async fn list_objects(State(state): State<AdminState>) -> Json<Vec<ObjectSummary>> {
+    let objects = state
+        .stores
+        .iter()
+        .flat_map(|(gvk, store)| {
+            store.state().into_iter().map(|obj| ObjectSummary {
+                gvk: gvk.clone(),
+                namespace: obj.metadata.namespace.clone(),
+                name: obj.metadata.name.clone().unwrap_or_default(),
+                resource_version: obj.metadata.resource_version.clone(),
+            })
+        })
+        .collect();
+    Json(objects)
+}
+
+/// Every `/objects/...` route is prefixed with `:gvk` (the same
+/// `resource::gvk_key` used to key `stores`): since a single process can
+/// watch several GVKs at once, the namespace/name pair alone isn't enough to
+/// pick a store.
+async fn get_namespaced_object(
+    State(state): State<AdminState>,
+    Path((gvk, namespace, name)): Path<(String, String, String)>,
+) -> Result<Json<DynamicObject>, StatusCode> {
+    find_object(&state, &gvk, Some(&namespace), &name)
+}
+
+/// Cluster-scoped objects have no namespace segment to match against; this
+/// route exists so they're addressable at all (the namespaced route above
+/// can never match an object whose `metadata.namespace` is `None`).
+async fn get_cluster_scoped_object(
+    State(state): State<AdminState>,
+    Path((gvk, name)): Path<(String, String)>,
+) -> Result<Json<DynamicObject>, StatusCode> {
+    find_object(&state, &gvk, None, &name)
+}
+
+fn find_object(
+    state: &AdminState,
+    gvk: &str,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<Json<DynamicObject>, StatusCode> {
+    let store = state.stores.get(gvk).ok_or(StatusCode::NOT_FOUND)?;
+    store
+        .state()
+        .iter()
+        .find(|obj| {
+            obj.metadata.namespace.as_deref() == namespace && obj.metadata.name.as_deref() == Some(name)
+        })
+        .map(|obj| {
+            let mut obj = (**obj).clone();
+            obj.metadata.managed_fields = None;
+            Json(obj)
+        })
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(State(state): State<AdminState>) -> StatusCode {
+    if state.readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
@@ -1,3 +1,4 @@
+use crate::quantity::Quantity;
 use k8s_openapi::schemars;
 
 #[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
@@ -35,8 +36,112 @@ impl k8s_openapi::DeepMerge for ProjectResourceQuota {
     }
 }
 
+impl ProjectResourceQuota {
+    /// The remaining headroom per resource, or `None` if this project has no
+    /// quota limit configured at all. Missing `used_limit` fields are treated
+    /// as zero usage.
+    pub fn remaining(&self) -> anyhow::Result<Option<ResourceQuotaRemaining>> {
+        match &self.limit {
+            None => Ok(None),
+            Some(limit) => {
+                let default_used = ResourceQuotaLimit::default();
+                let used = self.used_limit.as_ref().unwrap_or(&default_used);
+                Ok(Some(limit.remaining(used)?))
+            }
+        }
+    }
+}
+
+/// `ResourceQuotaLimit` as it existed on the older (v2) Rancher project schema,
+/// before `requestsStorage`, `servicesNodePorts` and `servicesLoadBalancers`
+/// were added.
 #[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
+pub struct ResourceQuotaLimitV2 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pods: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replication_controllers: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_maps: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistent_volume_claims: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_cpu: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_memory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits_cpu: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits_memory: Option<String>,
+}
+
+impl k8s_openapi::DeepMerge for ResourceQuotaLimitV2 {
+    fn merge_from(&mut self, other: Self)
+    where
+        Self: Sized,
+    {
+        self.pods.merge_from(other.pods);
+        self.services.merge_from(other.services);
+        self.replication_controllers
+            .merge_from(other.replication_controllers);
+        self.secrets.merge_from(other.secrets);
+        self.config_maps.merge_from(other.config_maps);
+        self.persistent_volume_claims
+            .merge_from(other.persistent_volume_claims);
+        self.requests_cpu.merge_from(other.requests_cpu);
+        self.requests_memory.merge_from(other.requests_memory);
+        self.limits_cpu.merge_from(other.limits_cpu);
+        self.limits_memory.merge_from(other.limits_memory);
+    }
+}
+
+impl From<ResourceQuotaLimitV2> for ResourceQuotaLimit {
+    fn from(v2: ResourceQuotaLimitV2) -> Self {
+        ResourceQuotaLimit {
+            pods: v2.pods,
+            services: v2.services,
+            replication_controllers: v2.replication_controllers,
+            secrets: v2.secrets,
+            config_maps: v2.config_maps,
+            persistent_volume_claims: v2.persistent_volume_claims,
+            services_node_ports: None,
+            services_load_balancers: None,
+            requests_cpu: v2.requests_cpu,
+            requests_memory: v2.requests_memory,
+            requests_storage: None,
+            limits_cpu: v2.limits_cpu,
+            limits_memory: v2.limits_memory,
+        }
+    }
+}
+
+/// Downgrading to v2 also drops `requestsStorage`, `servicesNodePorts` and
+/// `servicesLoadBalancers`: the older schema has no field to hold them, so
+/// this conversion is lossy, not just a rename.
+impl From<ResourceQuotaLimit> for ResourceQuotaLimitV2 {
+    fn from(v3: ResourceQuotaLimit) -> Self {
+        ResourceQuotaLimitV2 {
+            pods: v3.pods,
+            services: v3.services,
+            replication_controllers: v3.replication_controllers,
+            secrets: v3.secrets,
+            config_maps: v3.config_maps,
+            persistent_volume_claims: v3.persistent_volume_claims,
+            requests_cpu: v3.requests_cpu,
+            requests_memory: v3.requests_memory,
+            limits_cpu: v3.limits_cpu,
+            limits_memory: v3.limits_memory,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ResourceQuotaLimit {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pods: Option<String>,
@@ -91,6 +196,132 @@ impl k8s_openapi::DeepMerge for ResourceQuotaLimit {
     }
 }
 
+/// The remaining headroom for a single resource: `limit - used_limit`, plus
+/// whether usage has already exceeded the limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantityRemaining {
+    pub remaining: Quantity,
+    pub over_quota: bool,
+}
+
+/// Mirrors `ResourceQuotaLimit`, one `QuantityRemaining` per resource that had
+/// a limit configured.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceQuotaRemaining {
+    pub pods: Option<QuantityRemaining>,
+    pub services: Option<QuantityRemaining>,
+    pub replication_controllers: Option<QuantityRemaining>,
+    pub secrets: Option<QuantityRemaining>,
+    pub config_maps: Option<QuantityRemaining>,
+    pub persistent_volume_claims: Option<QuantityRemaining>,
+    pub services_node_ports: Option<QuantityRemaining>,
+    pub services_load_balancers: Option<QuantityRemaining>,
+    pub requests_cpu: Option<QuantityRemaining>,
+    pub requests_memory: Option<QuantityRemaining>,
+    pub requests_storage: Option<QuantityRemaining>,
+    pub limits_cpu: Option<QuantityRemaining>,
+    pub limits_memory: Option<QuantityRemaining>,
+}
+
+fn remaining_field(
+    limit: Option<&String>,
+    used: Option<&String>,
+) -> anyhow::Result<Option<QuantityRemaining>> {
+    let Some(limit) = limit else {
+        return Ok(None);
+    };
+    let limit: Quantity = limit.parse()?;
+    let used: Quantity = match used {
+        Some(used) => used.parse()?,
+        None => Quantity::ZERO,
+    };
+    Ok(Some(QuantityRemaining {
+        remaining: limit.saturating_sub(used),
+        over_quota: used.as_milli() > limit.as_milli(),
+    }))
+}
+
+impl ResourceQuotaLimit {
+    /// Computes the remaining headroom field-by-field against `used`. A field
+    /// with no configured limit is left out of the result entirely.
+    pub fn remaining(&self, used: &ResourceQuotaLimit) -> anyhow::Result<ResourceQuotaRemaining> {
+        Ok(ResourceQuotaRemaining {
+            pods: remaining_field(self.pods.as_ref(), used.pods.as_ref())?,
+            services: remaining_field(self.services.as_ref(), used.services.as_ref())?,
+            replication_controllers: remaining_field(
+                self.replication_controllers.as_ref(),
+                used.replication_controllers.as_ref(),
+            )?,
+            secrets: remaining_field(self.secrets.as_ref(), used.secrets.as_ref())?,
+            config_maps: remaining_field(self.config_maps.as_ref(), used.config_maps.as_ref())?,
+            persistent_volume_claims: remaining_field(
+                self.persistent_volume_claims.as_ref(),
+                used.persistent_volume_claims.as_ref(),
+            )?,
+            services_node_ports: remaining_field(
+                self.services_node_ports.as_ref(),
+                used.services_node_ports.as_ref(),
+            )?,
+            services_load_balancers: remaining_field(
+                self.services_load_balancers.as_ref(),
+                used.services_load_balancers.as_ref(),
+            )?,
+            requests_cpu: remaining_field(self.requests_cpu.as_ref(), used.requests_cpu.as_ref())?,
+            requests_memory: remaining_field(
+                self.requests_memory.as_ref(),
+                used.requests_memory.as_ref(),
+            )?,
+            requests_storage: remaining_field(
+                self.requests_storage.as_ref(),
+                used.requests_storage.as_ref(),
+            )?,
+            limits_cpu: remaining_field(self.limits_cpu.as_ref(), used.limits_cpu.as_ref())?,
+            limits_memory: remaining_field(
+                self.limits_memory.as_ref(),
+                used.limits_memory.as_ref(),
+            )?,
+        })
+    }
+}
+
+/// `ProjectResourceQuota` as it existed on the older (v2) Rancher project schema.
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectResourceQuotaV2 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<ResourceQuotaLimitV2>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_limit: Option<ResourceQuotaLimitV2>,
+}
+
+impl k8s_openapi::DeepMerge for ProjectResourceQuotaV2 {
+    fn merge_from(&mut self, other: Self)
+    where
+        Self: Sized,
+    {
+        self.limit.merge_from(other.limit);
+        self.used_limit.merge_from(other.used_limit);
+    }
+}
+
+impl From<ProjectResourceQuotaV2> for ProjectResourceQuota {
+    fn from(v2: ProjectResourceQuotaV2) -> Self {
+        ProjectResourceQuota {
+            limit: v2.limit.map(Into::into),
+            used_limit: v2.used_limit.map(Into::into),
+        }
+    }
+}
+
+impl From<ProjectResourceQuota> for ProjectResourceQuotaV2 {
+    fn from(v3: ProjectResourceQuota) -> Self {
+        ProjectResourceQuotaV2 {
+            limit: v3.limit.map(Into::into),
+            used_limit: v3.used_limit.map(Into::into),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContainerResourceLimit {
@@ -116,6 +347,57 @@ impl k8s_openapi::DeepMerge for ContainerResourceLimit {
     }
 }
 
+/// `ProjectSpec` as it existed on the older (v2) Rancher project schema: no
+/// `enableProjectMonitoring` toggle yet, and a narrower `ResourceQuotaLimit`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    k8s_openapi_derive::CustomResourceDefinition,
+    schemars::JsonSchema,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+#[custom_resource_definition(
+    group = "management.cattle.io",
+    version = "v2",
+    kind = "Project",
+    plural = "projects",
+    generate_schema,
+    namespaced,
+    impl_deep_merge
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSpecV2 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resource_quota: Option<ProjectResourceQuotaV2>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace_default_resource_quota: Option<NamespaceResourceQuota>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container_default_resource_limit: Option<ContainerResourceLimit>,
+}
+
+impl k8s_openapi::DeepMerge for ProjectSpecV2 {
+    fn merge_from(&mut self, other: Self)
+    where
+        Self: Sized,
+    {
+        self.display_name.merge_from(other.display_name);
+        self.description.merge_from(other.description);
+        self.cluster_name.merge_from(other.cluster_name);
+        self.resource_quota.merge_from(other.resource_quota);
+        self.namespace_default_resource_quota
+            .merge_from(other.namespace_default_resource_quota);
+        self.container_default_resource_limit
+            .merge_from(other.container_default_resource_limit);
+    }
+}
+
 #[derive(
     Clone,
     Debug,
@@ -128,13 +410,14 @@ impl k8s_openapi::DeepMerge for ContainerResourceLimit {
 #[custom_resource_definition(
     group = "management.cattle.io",
     version = "v3",
+    kind = "Project",
     plural = "projects",
     generate_schema,
     namespaced,
     impl_deep_merge
 )]
 #[serde(rename_all = "camelCase")]
-pub struct ProjectSpec {
+pub struct ProjectSpecV3 {
     #[serde(skip_serializing_if = "Option::is_none")]
     display_name: Option<String>,
     description: String,
@@ -149,7 +432,7 @@ pub struct ProjectSpec {
     enable_project_monitoring: bool,
 }
 
-impl k8s_openapi::DeepMerge for ProjectSpec {
+impl k8s_openapi::DeepMerge for ProjectSpecV3 {
     fn merge_from(&mut self, other: Self)
     where
         Self: Sized,
@@ -166,3 +449,199 @@ impl k8s_openapi::DeepMerge for ProjectSpec {
             .merge_from(other.enable_project_monitoring);
     }
 }
+
+/// `ProjectSpecV3` is the storage version; upgrading from v2 fills in the
+/// fields it doesn't have with their documented defaults.
+impl From<ProjectSpecV2> for ProjectSpecV3 {
+    fn from(v2: ProjectSpecV2) -> Self {
+        ProjectSpecV3 {
+            display_name: v2.display_name,
+            description: v2.description,
+            cluster_name: v2.cluster_name,
+            resource_quota: v2.resource_quota.map(Into::into),
+            namespace_default_resource_quota: v2.namespace_default_resource_quota,
+            container_default_resource_limit: v2.container_default_resource_limit,
+            enable_project_monitoring: false,
+        }
+    }
+}
+
+/// Downgrading from v3 to v2 drops `enableProjectMonitoring` (older clients
+/// never knew about it) and, via `ResourceQuotaLimitV2`'s `From` impl, also
+/// drops `requestsStorage`, `servicesNodePorts` and `servicesLoadBalancers`
+/// from any configured resource quota. None of that is recoverable once a v2
+/// client writes the object back.
+impl From<ProjectSpecV3> for ProjectSpecV2 {
+    fn from(v3: ProjectSpecV3) -> Self {
+        ProjectSpecV2 {
+            display_name: v3.display_name,
+            description: v3.description,
+            cluster_name: v3.cluster_name,
+            resource_quota: v3.resource_quota.map(Into::into),
+            namespace_default_resource_quota: v3.namespace_default_resource_quota,
+            container_default_resource_limit: v3.container_default_resource_limit,
+        }
+    }
+}
+
+/// Builds the combined `CustomResourceDefinition` manifest for `Project`,
+/// listing every served version (`v2`, `v3`) with `v3` marked as storage.
+pub fn project_crd() -> anyhow::Result<k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition> {
+    kube::core::crd::merge_crds(vec![ProjectV2::crd(), ProjectV3::crd()], "v3")
+        .map_err(|e| anyhow::anyhow!("failed to merge Project CRD versions: {e}"))
+}
+
+/// Reads a watched `Project` object's `.spec` (in whichever served version it
+/// arrived as, upgrading v2 to v3 via `From`) and reports its resource-quota
+/// headroom. Returns `None` for objects with no resource quota configured.
+pub fn project_quota_remaining(spec: &serde_json::Value) -> anyhow::Result<Option<ResourceQuotaRemaining>> {
+    let spec: ProjectSpecV3 = match serde_json::from_value::<ProjectSpecV3>(spec.clone()) {
+        Ok(v3) => v3,
+        Err(_) => serde_json::from_value::<ProjectSpecV2>(spec.clone())?.into(),
+    };
+    match &spec.resource_quota {
+        Some(quota) => quota.remaining(),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(
+        pods: Option<&str>,
+        requests_cpu: Option<&str>,
+        requests_memory: Option<&str>,
+    ) -> ResourceQuotaLimit {
+        ResourceQuotaLimit {
+            pods: pods.map(String::from),
+            requests_cpu: requests_cpu.map(String::from),
+            requests_memory: requests_memory.map(String::from),
+            ..ResourceQuotaLimit::default()
+        }
+    }
+
+    #[test]
+    fn remaining_reports_headroom_for_configured_fields_only() {
+        let limit = limit(Some("10"), Some("2"), Some("4Gi"));
+        let used = limit_used(Some("4"), Some("1"), None);
+
+        let remaining = limit.remaining(&used).unwrap();
+
+        assert_eq!(remaining.pods.unwrap().remaining.as_milli(), 6_000);
+        assert!(!remaining.pods.unwrap().over_quota);
+        assert_eq!(remaining.requests_cpu.unwrap().remaining.as_milli(), 1_000);
+        // used_limit left this field unset, so it's treated as zero usage.
+        assert_eq!(
+            remaining.requests_memory.unwrap().remaining.as_milli(),
+            "4Gi".parse::<Quantity>().unwrap().as_milli()
+        );
+        assert!(remaining.services.is_none());
+    }
+
+    fn limit_used(
+        pods: Option<&str>,
+        requests_cpu: Option<&str>,
+        requests_memory: Option<&str>,
+    ) -> ResourceQuotaLimit {
+        limit(pods, requests_cpu, requests_memory)
+    }
+
+    #[test]
+    fn remaining_flags_over_quota() {
+        let limit = limit(Some("1"), None, None);
+        let used = limit_used(Some("2"), None, None);
+
+        let remaining = limit.remaining(&used).unwrap();
+
+        let pods = remaining.pods.unwrap();
+        assert!(pods.over_quota);
+        assert_eq!(pods.remaining.as_milli(), -1_000);
+    }
+
+    #[test]
+    fn remaining_rejects_malformed_quantities() {
+        let limit = limit(Some("not-a-quantity"), None, None);
+        assert!(limit.remaining(&ResourceQuotaLimit::default()).is_err());
+    }
+
+    #[test]
+    fn project_resource_quota_remaining_is_none_without_a_limit() {
+        let quota = ProjectResourceQuota {
+            limit: None,
+            used_limit: None,
+        };
+        assert!(quota.remaining().unwrap().is_none());
+    }
+
+    #[test]
+    fn resource_quota_limit_v2_v3_round_trips_shared_fields() {
+        let v2 = ResourceQuotaLimitV2 {
+            pods: Some("10".to_string()),
+            services: Some("5".to_string()),
+            replication_controllers: None,
+            secrets: None,
+            config_maps: None,
+            persistent_volume_claims: None,
+            requests_cpu: Some("2".to_string()),
+            requests_memory: Some("4Gi".to_string()),
+            limits_cpu: None,
+            limits_memory: None,
+        };
+
+        let v3: ResourceQuotaLimit = v2.clone().into();
+        assert_eq!(v3.pods, v2.pods);
+        assert_eq!(v3.services, v2.services);
+        assert_eq!(v3.requests_cpu, v2.requests_cpu);
+        assert_eq!(v3.requests_memory, v2.requests_memory);
+        assert_eq!(v3.requests_storage, None);
+        assert_eq!(v3.services_node_ports, None);
+        assert_eq!(v3.services_load_balancers, None);
+
+        let back: ResourceQuotaLimitV2 = v3.into();
+        assert_eq!(back, v2);
+    }
+
+    #[test]
+    fn project_spec_v2_to_v3_upgrade_defaults_enable_project_monitoring_false() {
+        let v2 = ProjectSpecV2 {
+            display_name: Some("team-a".to_string()),
+            description: "Team A's project".to_string(),
+            cluster_name: Some("local".to_string()),
+            resource_quota: None,
+            namespace_default_resource_quota: None,
+            container_default_resource_limit: None,
+        };
+
+        let v3: ProjectSpecV3 = v2.clone().into();
+        assert_eq!(v3.display_name, v2.display_name);
+        assert_eq!(v3.description, v2.description);
+        assert!(!v3.enable_project_monitoring);
+
+        let back: ProjectSpecV2 = v3.into();
+        assert_eq!(back, v2);
+    }
+
+    #[test]
+    fn project_quota_remaining_upgrades_v2_spec_before_computing() {
+        let v2_spec = serde_json::json!({
+            "description": "team-a",
+            "resourceQuota": {
+                "limit": { "pods": "10" },
+                "usedLimit": { "pods": "4" },
+            },
+        });
+
+        let remaining = project_quota_remaining(&v2_spec).unwrap().unwrap();
+        let pods = remaining.pods.unwrap();
+        assert_eq!(pods.remaining.as_milli(), 6_000);
+        assert!(!pods.over_quota);
+    }
+
+    #[test]
+    fn project_quota_remaining_is_none_without_resource_quota() {
+        let spec = serde_json::json!({ "description": "team-a", "enableProjectMonitoring": false });
+        assert!(project_quota_remaining(&spec).unwrap().is_none());
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+/// The three kinds of change a reflector event can represent.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Applied,
+    Deleted,
+    Restarted,
+}
+
+/// A normalized, serializable view of a `watcher::Event`, broadcast to
+/// in-process subscribers and, optionally, POSTed to a webhook.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChangeRecord {
+    pub kind: ChangeKind,
+    pub gvk: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_version: Option<String>,
+}
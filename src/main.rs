@@ -6,125 +6,289 @@ use kube::client::Client;
 use kube::runtime::reflector::store::Writer;
 use kube::runtime::{reflector, watcher, watcher::Config, WatchStreamExt};
 
+mod admin;
 mod custom_resources;
+mod events;
+mod metrics;
+mod quantity;
+mod resource;
+mod webhook;
+
+use admin::Readiness;
+use events::{ChangeKind, ChangeRecord};
+use metrics::{Metrics, MetricsExporter};
+use resource::{build_api_resource, ResourceSpec};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
-    /// api and version of the resource (e.g.: "networking.k8s.io/v1")
-    #[clap(long)]
-    apiversion: String,
+    /// resource to watch, repeatable: "apiVersion=...,kind=...[,namespace=...|,global]"
+    /// (e.g.: "apiVersion=networking.k8s.io/v1,kind=Ingress,namespace=default")
+    #[clap(long = "resource", required_unless_present = "command")]
+    resources: Vec<ResourceSpec>,
 
-    /// Kind of the resource (e.g: "Ingress")
+    /// expose a Prometheus `/metrics` endpoint on this address (e.g.: "0.0.0.0:9090")
     #[clap(long)]
-    kind: String,
+    metrics_addr: Option<std::net::SocketAddr>,
 
-    /// namespace to be used
+    /// push metrics to this OTLP collector endpoint instead of serving Prometheus
+    #[clap(long, conflicts_with = "metrics_addr")]
+    otlp_endpoint: Option<String>,
+
+    /// expose a read-only admin API (`/objects`, `/healthz`, `/readyz`) on this address
     #[clap(long)]
-    namespace: Option<String>,
+    admin_addr: Option<std::net::SocketAddr>,
 
-    /// query for the resource globally
+    /// POST a JSON change record to this URL for every applied/deleted event
     #[clap(long)]
-    global: bool,
-}
+    webhook_url: Option<String>,
 
-#[derive(Debug)]
-struct KubeResource {
-    pub resource: kube::api::ApiResource,
-    pub namespaced: bool,
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-async fn build_api_resource(client: &Client, apiversion: &str, kind: &str) -> Result<KubeResource> {
-    let resources_list = match apiversion {
-        "v1" => client.list_core_api_resources(apiversion).await?,
-        _ => client.list_api_group_resources(apiversion).await?,
-    };
-
-    let (group, version) = match apiversion {
-        "v1" => ("", "v1"),
-        _ => apiversion
-            .split_once('/')
-            .ok_or_else(|| anyhow!("cannot determine group and version for {apiversion}"))?,
-    };
-
-    let resource = resources_list
-        .resources
-        .iter()
-        .find(|r| r.kind == kind)
-        .ok_or_else(|| anyhow!("Cannot find resource {apiversion}/{kind}"))?
-        .clone();
-
-    Ok(KubeResource {
-        resource: kube::api::ApiResource {
-            group: group.to_string(),
-            version: version.to_string(),
-            api_version: apiversion.to_string(),
-            kind: kind.to_string(),
-            plural: resource.name,
-        },
-        namespaced: resource.namespaced,
-    })
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Emit the combined multi-version CustomResourceDefinition manifest for Project
+    Crd,
 }
 
 use futures::Stream;
 use kube::runtime::reflector::store;
 use kube::Resource;
 use std::hash::Hash;
-pub fn my_reflector<K, W>(mut writer: store::Writer<K>, stream: W) -> impl Stream<Item = W::Item>
+pub fn my_reflector<K, W>(
+    mut writer: store::Writer<K>,
+    stream: W,
+    metrics: Option<Metrics>,
+    api_version: String,
+    kind: String,
+    readiness: Option<Readiness>,
+    changes: Option<tokio::sync::broadcast::Sender<ChangeRecord>>,
+) -> impl Stream<Item = W::Item>
 where
     K: Resource + Clone,
     K::DynamicType: Eq + Hash + Clone,
     W: Stream<Item = watcher::Result<watcher::Event<K>>>,
 {
+    let gvk = format!("{api_version}/{kind}");
     stream.inspect_ok(move |event| {
         match event {
-            watcher::Event::Applied(_) => println!("apply"),
-            watcher::Event::Deleted(_) => println!("deleted"),
-            watcher::Event::Restarted(items) => println!("restarted {}", items.len()),
+            watcher::Event::Applied(obj) => {
+                println!("apply");
+                if let Some(m) = &metrics {
+                    m.record_event("applied", &api_version, &kind);
+                }
+                publish_change(&changes, ChangeKind::Applied, &gvk, obj);
+            }
+            watcher::Event::Deleted(obj) => {
+                println!("deleted");
+                if let Some(m) = &metrics {
+                    m.record_event("deleted", &api_version, &kind);
+                }
+                publish_change(&changes, ChangeKind::Deleted, &gvk, obj);
+            }
+            watcher::Event::Restarted(items) => {
+                println!("restarted {}", items.len());
+                if let Some(m) = &metrics {
+                    m.record_event("restarted", &api_version, &kind);
+                    m.record_restart(items.len() as u64, &api_version, &kind);
+                }
+                if let Some(r) = &readiness {
+                    r.mark_ready(&gvk);
+                }
+                // The initial list-on-watch only ever arrives here, as one
+                // batch, never as individual Applied events. Fan each item
+                // out as its own Applied record so subscribers (the webhook,
+                // in particular) actually see the startup inventory instead
+                // of silently missing it, but also send a single Restarted
+                // marker first so a subscriber can tell "these N applies are
+                // one resync" apart from N genuine individual applies.
+                if let Some(tx) = &changes {
+                    let _ = tx.send(ChangeRecord {
+                        kind: ChangeKind::Restarted,
+                        gvk: gvk.clone(),
+                        namespace: None,
+                        name: None,
+                        resource_version: None,
+                    });
+                }
+                for item in items {
+                    publish_change(&changes, ChangeKind::Applied, &gvk, item);
+                }
+            }
         }
         writer.apply_watcher_event(event)
     })
 }
 
+/// Logs a Project object's resource-quota headroom, if it has any quota
+/// configured. Used for both `Applied` events and each item of a `Restarted`
+/// batch, so quota pressure is reported for the existing inventory at
+/// startup, not only for objects touched afterward.
+fn log_project_quota_remaining(obj: &kube::core::DynamicObject) {
+    let Some(spec_value) = obj.data.get("spec") else {
+        return;
+    };
+    match custom_resources::project_quota_remaining(spec_value) {
+        Ok(Some(remaining)) => println!(
+            "quota remaining for Project/{}: {remaining:?}",
+            obj.metadata.name.clone().unwrap_or_default()
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!(
+            "failed to compute quota remaining for Project/{}: {e}",
+            obj.metadata.name.clone().unwrap_or_default()
+        ),
+    }
+}
+
+fn publish_change<K: Resource>(
+    changes: &Option<tokio::sync::broadcast::Sender<ChangeRecord>>,
+    kind: ChangeKind,
+    gvk: &str,
+    obj: &K,
+) {
+    if let Some(tx) = changes {
+        let meta = obj.meta();
+        let _ = tx.send(ChangeRecord {
+            kind,
+            gvk: gvk.to_string(),
+            namespace: meta.namespace.clone(),
+            name: meta.name.clone(),
+            resource_version: meta.resource_version.clone(),
+        });
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    if cli.namespace.is_some() && cli.global {
-        return Err(anyhow!(
-            "cannot specify a namespace and the global flag at the same time"
-        ));
+    if let Some(Command::Crd) = cli.command {
+        let crd = custom_resources::project_crd()?;
+        print!("{}", serde_yaml::to_string(&crd)?);
+        return Ok(());
+    }
+    if cli.resources.is_empty() {
+        return Err(anyhow!("at least one --resource is required"));
     }
 
     let client = Client::try_default().await?;
 
-    let resource = build_api_resource(&client, &cli.apiversion, &cli.kind).await?;
-    println!("{}/{} => {resource:?}", cli.apiversion, cli.kind);
+    let metrics = if let Some(addr) = cli.metrics_addr {
+        Some(Metrics::init(&MetricsExporter::Prometheus { addr }).await?)
+    } else if let Some(endpoint) = cli.otlp_endpoint.clone() {
+        Some(Metrics::init(&MetricsExporter::Otlp { endpoint }).await?)
+    } else {
+        None
+    };
+
+    let readiness = if cli.admin_addr.is_some() {
+        Some(Readiness::new(cli.resources.len()))
+    } else {
+        None
+    };
+
+    // The broadcast sender stays cloneable so future in-process consumers
+    // (metrics, the admin API, or whatever comes next) can subscribe
+    // independently of the webhook notifier.
+    let (change_tx, _) = tokio::sync::broadcast::channel::<ChangeRecord>(1024);
+    if let Some(webhook_url) = cli.webhook_url.clone() {
+        let rx = change_tx.subscribe();
+        tokio::spawn(webhook::run(webhook_url, rx));
+    }
+
+    // Each resource gets its own `Writer`/`Reader` and watcher stream (and
+    // thus its own cache and backoff), driven concurrently on the shared
+    // `Client` by merging every touched-object stream below.
+    let mut admin_stores = std::collections::HashMap::new();
+    let mut watches: Vec<
+        std::pin::Pin<Box<dyn Stream<Item = kube::core::DynamicObject> + Send>>,
+    > = Vec::new();
+
+    for spec in &cli.resources {
+        let resource = build_api_resource(&client, &spec.apiversion, &spec.kind).await?;
+        println!("{}/{} => {resource:?}", spec.apiversion, spec.kind);
 
-    let api = if !cli.global && resource.namespaced {
-        match cli.namespace {
-            Some(namespace) => kube::api::Api::<kube::core::DynamicObject>::namespaced_with(
+        let api = if !spec.global && resource.namespaced {
+            match &spec.namespace {
+                Some(namespace) => kube::api::Api::<kube::core::DynamicObject>::namespaced_with(
+                    client.clone(),
+                    namespace,
+                    &resource.resource,
+                ),
+                None => return Err(anyhow!("No namespace provided for a namespaced resource")),
+            }
+        } else {
+            kube::api::Api::<kube::core::DynamicObject>::all_with(
                 client.clone(),
-                &namespace,
                 &resource.resource,
-            ),
-            None => return Err(anyhow!("No namespace provided for a namespaced resource")),
+            )
+        };
+
+        let writer = Writer::new(resource.resource);
+        let reader = writer.as_reader();
+        admin_stores.insert(spec.gvk_key(), reader.clone());
+
+        if let Some(m) = &metrics {
+            let reader_for_gauge = reader.clone();
+            m.track_store_size(spec.apiversion.clone(), spec.kind.clone(), move || {
+                reader_for_gauge.len()
+            });
         }
-    } else {
-        kube::api::Api::<kube::core::DynamicObject>::all_with(client, &resource.resource)
-    };
 
-    let writer = Writer::new(resource.resource);
-    let reader = writer.as_reader();
-    let filter = Config::default();
-    let stream = watcher(api, filter).map_ok(|ev| {
-        ev.modify(|obj| {
-            // clear managed fields to reduce memory usage
-            obj.metadata.managed_fields = None;
-        })
-    });
-    let rf = my_reflector(writer, stream);
+        let filter = Config::default();
+        let stream = watcher(api, filter).map_ok(|ev| {
+            ev.modify(|obj| {
+                // clear managed fields to reduce memory usage
+                obj.metadata.managed_fields = None;
+            })
+        });
+        // Project is the only GVK with a resource quota to report on; every
+        // other kind's spec just won't have a "spec.resourceQuota" to find.
+        let quota_kind = spec.kind.clone();
+        let stream = stream.inspect_ok(move |ev| {
+            if quota_kind != "Project" {
+                return;
+            }
+            match ev {
+                watcher::Event::Applied(obj) => log_project_quota_remaining(obj),
+                // The initial list-on-watch arrives here, as one batch, not
+                // as individual Applied events: without this arm, a mostly
+                // static cluster would never have its existing projects'
+                // quota pressure reported until something happened to touch
+                // one of them.
+                watcher::Event::Restarted(items) => {
+                    for obj in items {
+                        log_project_quota_remaining(obj);
+                    }
+                }
+                watcher::Event::Deleted(_) => {}
+            }
+        });
+        let rf = my_reflector(
+            writer,
+            stream,
+            metrics.clone(),
+            spec.apiversion.clone(),
+            spec.kind.clone(),
+            readiness.clone(),
+            Some(change_tx.clone()),
+        );
+
+        watches.push(Box::pin(rf.default_backoff().touched_objects()));
+    }
+
+    if let Some(admin_addr) = cli.admin_addr {
+        let admin_readiness = readiness.clone().expect("readiness set above");
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(admin_addr, admin_stores, admin_readiness).await {
+                eprintln!("admin API server exited: {e}");
+            }
+        });
+    }
 
-    let infinite_watch = rf.default_backoff().touched_objects().for_each(|o| {
+    let infinite_watch = futures::stream::select_all(watches).for_each(|o| {
         dbg!(o);
         ready(())
     });
@@ -0,0 +1,177 @@
+use anyhow::{Context, Result};
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::{global, KeyValue};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// How the process should hand off the instruments it records.
+#[derive(Clone, Debug)]
+pub enum MetricsExporter {
+    /// Serve `/metrics` in Prometheus text format at `addr`.
+    Prometheus { addr: SocketAddr },
+    /// Push metrics periodically to an OTLP collector.
+    Otlp { endpoint: String },
+}
+
+/// A reflector store registered with `track_store_size`, polled by the single
+/// shared `reflector_store_size` gauge callback on every collection.
+struct StoreSizeSource {
+    api_version: String,
+    kind: String,
+    reader_len: Box<dyn Fn() -> usize + Send + Sync>,
+}
+
+/// The instruments shared by every watched resource. Cheap to clone: everything
+/// here is a handle into the global `MeterProvider`, or a handle into state
+/// shared with it.
+#[derive(Clone)]
+pub struct Metrics {
+    events_total: Counter<u64>,
+    restart_objects: Histogram<u64>,
+    store_size_sources: Arc<Mutex<Vec<StoreSizeSource>>>,
+}
+
+impl Metrics {
+    /// Installs a `MeterProvider` for `exporter` as the global default and
+    /// returns a handle that `my_reflector` can use to record instruments.
+    ///
+    /// For the Prometheus case this also spawns the `/metrics` HTTP server;
+    /// the OTLP case pushes on its own interval and needs nothing further.
+    pub async fn init(exporter: &MetricsExporter) -> Result<Self> {
+        match exporter {
+            MetricsExporter::Prometheus { addr } => Self::init_prometheus(*addr).await,
+            MetricsExporter::Otlp { endpoint } => Self::init_otlp(endpoint),
+        }
+    }
+
+    async fn init_prometheus(addr: SocketAddr) -> Result<Self> {
+        let registry = prometheus::Registry::new();
+        let exporter = opentelemetry_prometheus::exporter()
+            .with_registry(registry.clone())
+            .build()
+            .context("failed to build the Prometheus exporter")?;
+        let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
+            .with_reader(exporter)
+            .build();
+        global::set_meter_provider(provider);
+
+        let app = axum::Router::new().route(
+            "/metrics",
+            axum::routing::get(move || serve_prometheus(registry.clone())),
+        );
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to bind metrics server on {addr}"))?;
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                eprintln!("metrics server exited: {e}");
+            }
+        });
+
+        Ok(Self::from_meter(global::meter("rust-client-watcher")))
+    }
+
+    fn init_otlp(endpoint: &str) -> Result<Self> {
+        let exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .context("failed to build the OTLP metric exporter")?;
+        let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
+            .with_periodic_exporter(exporter)
+            .build();
+        global::set_meter_provider(provider);
+
+        Ok(Self::from_meter(global::meter("rust-client-watcher")))
+    }
+
+    /// Builds every instrument exactly once: two counters/histograms created
+    /// up front, plus a single `reflector_store_size` gauge whose callback
+    /// observes every store registered later via `track_store_size`. Building
+    /// instruments on each recorded event, or registering a fresh gauge per
+    /// GVK under the same name, both confuse the exporter (duplicate
+    /// instruments) and waste allocations.
+    fn from_meter(meter: Meter) -> Self {
+        let events_total = meter
+            .u64_counter("watcher_events_total")
+            .with_description("Number of reflector events observed, labeled by kind and GVK")
+            .build();
+        let restart_objects = meter
+            .u64_histogram("watcher_restart_objects")
+            .with_description("Number of objects delivered in a single Restarted event")
+            .build();
+
+        let store_size_sources: Arc<Mutex<Vec<StoreSizeSource>>> = Arc::new(Mutex::new(Vec::new()));
+        let sources_for_callback = store_size_sources.clone();
+        meter
+            .u64_observable_gauge("reflector_store_size")
+            .with_description("Number of objects currently cached by the reflector store")
+            .with_callback(move |observer| {
+                for source in sources_for_callback.lock().unwrap().iter() {
+                    observer.observe(
+                        (source.reader_len)() as u64,
+                        &[
+                            KeyValue::new("api_version", source.api_version.clone()),
+                            KeyValue::new("kind", source.kind.clone()),
+                        ],
+                    );
+                }
+            })
+            .build();
+
+        Metrics {
+            events_total,
+            restart_objects,
+            store_size_sources,
+        }
+    }
+
+    /// Records one reflector event for `(api_version, kind)`, where `event_kind`
+    /// is one of `"applied"`, `"deleted"`, `"restarted"`.
+    pub fn record_event(&self, event_kind: &'static str, api_version: &str, kind: &str) {
+        self.events_total.add(
+            1,
+            &[
+                KeyValue::new("event", event_kind),
+                KeyValue::new("api_version", api_version.to_string()),
+                KeyValue::new("kind", kind.to_string()),
+            ],
+        );
+    }
+
+    /// Records the number of objects delivered by a `Restarted` event.
+    pub fn record_restart(&self, items: u64, api_version: &str, kind: &str) {
+        self.restart_objects.record(
+            items,
+            &[
+                KeyValue::new("api_version", api_version.to_string()),
+                KeyValue::new("kind", kind.to_string()),
+            ],
+        );
+    }
+
+    /// Registers a reflector store with the shared `reflector_store_size`
+    /// gauge. `reader_len` is typically `move || reader.len()` for the
+    /// `Reader` handle of that store.
+    pub fn track_store_size<F>(&self, api_version: String, kind: String, reader_len: F)
+    where
+        F: Fn() -> usize + Send + Sync + 'static,
+    {
+        self.store_size_sources.lock().unwrap().push(StoreSizeSource {
+            api_version,
+            kind,
+            reader_len: Box::new(reader_len),
+        });
+    }
+}
+
+async fn serve_prometheus(registry: prometheus::Registry) -> String {
+    use prometheus::Encoder;
+    let encoder = prometheus::TextEncoder::new();
+    let metric_families = registry.gather();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
+        eprintln!("failed to encode Prometheus metrics: {e}");
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed Kubernetes resource quantity (e.g. `"2Gi"`, `"500m"`), stored as
+/// milli-units in an `i128` so comparisons and subtraction never drift the
+/// way repeated float math would.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Quantity(i128);
+
+impl Quantity {
+    pub const ZERO: Quantity = Quantity(0);
+
+    /// The quantity's value in milli-units (i.e. `1_000` == one whole unit).
+    pub fn as_milli(self) -> i128 {
+        self.0
+    }
+
+    pub fn saturating_sub(self, other: Quantity) -> Quantity {
+        Quantity(self.0.saturating_sub(other.0))
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow!("'{s}' is not a valid Kubernetes quantity"));
+        }
+
+        let suffix_start = trimmed
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(trimmed.len());
+        let (number, suffix) = trimmed.split_at(suffix_start);
+
+        let (scale_num, scale_den): (i128, i128) = match suffix {
+            "" => (1, 1),
+            "n" => (1, 1_000_000_000),
+            "u" => (1, 1_000_000),
+            "m" => (1, 1_000),
+            "k" | "K" => (1_000, 1),
+            "M" => (1_000_000, 1),
+            "G" => (1_000_000_000, 1),
+            "T" => (1_000_000_000_000, 1),
+            "P" => (1_000_000_000_000_000, 1),
+            "E" => (1_000_000_000_000_000_000, 1),
+            "Ki" => (1_024, 1),
+            "Mi" => (1_024i128.pow(2), 1),
+            "Gi" => (1_024i128.pow(3), 1),
+            "Ti" => (1_024i128.pow(4), 1),
+            "Pi" => (1_024i128.pow(5), 1),
+            "Ei" => (1_024i128.pow(6), 1),
+            other => return Err(anyhow!("unknown quantity suffix '{other}' in '{s}'")),
+        };
+
+        let negative = number.starts_with('-');
+        let unsigned = number.trim_start_matches(['+', '-']);
+        let (int_part, frac_part) = match unsigned.split_once('.') {
+            Some((i, f)) => (i, f),
+            None => (unsigned, ""),
+        };
+        // A bare suffix (e.g. "Gi", "m") has no magnitude at all and must be
+        // rejected rather than silently treated as zero.
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(anyhow!("'{s}' is not a valid Kubernetes quantity"));
+        }
+        let digits = format!("{}{}", if int_part.is_empty() { "0" } else { int_part }, frac_part);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(anyhow!("'{s}' is not a valid Kubernetes quantity"));
+        }
+
+        let value: i128 = digits
+            .parse()
+            .map_err(|_| anyhow!("'{s}' is not a valid Kubernetes quantity"))?;
+        let frac_len = frac_part.len() as u32;
+
+        // milli = value * 1000 * scale_num / (10^frac_len * scale_den), rounded
+        // to the nearest milli-unit (half away from zero).
+        let numerator = value * 1_000 * scale_num;
+        let denominator = 10i128.pow(frac_len) * scale_den;
+        let mut milli = numerator / denominator;
+        if (numerator % denominator) * 2 >= denominator {
+            milli += 1;
+        }
+
+        Ok(Quantity(if negative { -milli } else { milli }))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / 1_000;
+        let frac = (self.0 % 1_000).abs();
+        if frac == 0 {
+            write!(f, "{whole}")
+        } else if whole == 0 && self.0 < 0 {
+            // -100 milli is -0.100, not 0.100: `whole` alone loses the sign
+            // when the magnitude is under one whole unit.
+            write!(f, "-0.{frac:03}")
+        } else {
+            write!(f, "{whole}.{frac:03}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_milli_cpu() {
+        assert_eq!("500m".parse::<Quantity>().unwrap().as_milli(), 500);
+    }
+
+    #[test]
+    fn parses_binary_suffix() {
+        assert_eq!(
+            "2Gi".parse::<Quantity>().unwrap().as_milli(),
+            2 * 1_024i128.pow(3) * 1_000
+        );
+    }
+
+    #[test]
+    fn parses_decimal_with_fraction_and_si_suffix() {
+        assert_eq!(
+            "1.5G".parse::<Quantity>().unwrap().as_milli(),
+            1_500_000_000_000
+        );
+    }
+
+    #[test]
+    fn parses_bare_integer() {
+        assert_eq!("42".parse::<Quantity>().unwrap().as_milli(), 42_000);
+    }
+
+    #[test]
+    fn parses_negative_quantity() {
+        let q = "-250m".parse::<Quantity>().unwrap();
+        assert_eq!(q.as_milli(), -250);
+        assert_eq!(q.to_string(), "-0.250");
+    }
+
+    #[test]
+    fn rejects_bare_suffix_with_no_magnitude() {
+        assert!("Gi".parse::<Quantity>().is_err());
+        assert!("m".parse::<Quantity>().is_err());
+        assert!("k".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_suffix() {
+        assert!("5Qi".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!("not-a-quantity".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn display_roundtrips_whole_and_fractional_values() {
+        assert_eq!("2".parse::<Quantity>().unwrap().to_string(), "2");
+        assert_eq!("2.5".parse::<Quantity>().unwrap().to_string(), "2.500");
+    }
+
+    #[test]
+    fn saturating_sub_reports_negative_headroom_when_over_quota() {
+        let limit = "1".parse::<Quantity>().unwrap();
+        let used = "1.5".parse::<Quantity>().unwrap();
+        let remaining = limit.saturating_sub(used);
+        assert_eq!(remaining.as_milli(), -500);
+        assert_eq!(remaining.to_string(), "-0.500");
+    }
+}
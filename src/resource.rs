@@ -0,0 +1,108 @@
+use anyhow::{anyhow, Result};
+use kube::client::Client;
+use std::str::FromStr;
+
+/// One `--resource` spec: which GVK to watch, and how.
+#[derive(Clone, Debug)]
+pub struct ResourceSpec {
+    pub apiversion: String,
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub global: bool,
+}
+
+/// A unique, URL-safe key for a `(apiversion, kind)` pair, used to keep the
+/// admin API and metrics from colliding when several GVKs are watched at once.
+pub fn gvk_key(apiversion: &str, kind: &str) -> String {
+    format!("{kind}.{}", apiversion.replace('/', "_"))
+}
+
+impl ResourceSpec {
+    pub fn gvk_key(&self) -> String {
+        gvk_key(&self.apiversion, &self.kind)
+    }
+}
+
+/// Parses `apiVersion=...,kind=...[,namespace=...|,global]`, e.g.:
+/// `apiVersion=networking.k8s.io/v1,kind=Ingress,namespace=default`.
+impl FromStr for ResourceSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut apiversion = None;
+        let mut kind = None;
+        let mut namespace = None;
+        let mut global = false;
+
+        for field in s.split(',') {
+            let field = field.trim();
+            if field.is_empty() {
+                continue;
+            }
+            match field.split_once('=') {
+                Some(("apiVersion", v)) => apiversion = Some(v.to_string()),
+                Some(("kind", v)) => kind = Some(v.to_string()),
+                Some(("namespace", v)) => namespace = Some(v.to_string()),
+                Some((key, _)) => return Err(anyhow!("unknown field '{key}' in --resource spec")),
+                None if field == "global" => global = true,
+                None => return Err(anyhow!("cannot parse '{field}' in --resource spec")),
+            }
+        }
+
+        if namespace.is_some() && global {
+            return Err(anyhow!(
+                "cannot specify a namespace and the global flag at the same time"
+            ));
+        }
+
+        Ok(ResourceSpec {
+            apiversion: apiversion
+                .ok_or_else(|| anyhow!("--resource spec is missing 'apiVersion='"))?,
+            kind: kind.ok_or_else(|| anyhow!("--resource spec is missing 'kind='"))?,
+            namespace,
+            global,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct KubeResource {
+    pub resource: kube::api::ApiResource,
+    pub namespaced: bool,
+}
+
+pub async fn build_api_resource(
+    client: &Client,
+    apiversion: &str,
+    kind: &str,
+) -> Result<KubeResource> {
+    let resources_list = match apiversion {
+        "v1" => client.list_core_api_resources(apiversion).await?,
+        _ => client.list_api_group_resources(apiversion).await?,
+    };
+
+    let (group, version) = match apiversion {
+        "v1" => ("", "v1"),
+        _ => apiversion
+            .split_once('/')
+            .ok_or_else(|| anyhow!("cannot determine group and version for {apiversion}"))?,
+    };
+
+    let resource = resources_list
+        .resources
+        .iter()
+        .find(|r| r.kind == kind)
+        .ok_or_else(|| anyhow!("Cannot find resource {apiversion}/{kind}"))?
+        .clone();
+
+    Ok(KubeResource {
+        resource: kube::api::ApiResource {
+            group: group.to_string(),
+            version: version.to_string(),
+            api_version: apiversion.to_string(),
+            kind: kind.to_string(),
+            plural: resource.name,
+        },
+        namespaced: resource.namespaced,
+    })
+}
@@ -0,0 +1,63 @@
+use crate::events::ChangeRecord;
+use backoff::ExponentialBackoff;
+use tokio::sync::broadcast;
+
+/// Mirrors the watcher stream's own retry policy: back off exponentially with
+/// no overall deadline, since a notifier that gives up for good would silently
+/// drop change records forever.
+fn default_backoff() -> ExponentialBackoff {
+    ExponentialBackoff {
+        max_elapsed_time: None,
+        ..ExponentialBackoff::default()
+    }
+}
+
+/// A 4xx response means the endpoint itself rejects the request (bad URL,
+/// auth, malformed payload) — retrying won't change the outcome, and doing
+/// so forever would block this record's sequential successors until the
+/// broadcast buffer overflows and later change records get silently
+/// dropped. 5xx and connection-level failures are treated as transient, same
+/// as before.
+fn classify_error(err: reqwest::Error) -> backoff::Error<reqwest::Error> {
+    match err.status() {
+        Some(status) if status.is_client_error() => backoff::Error::permanent(err),
+        _ => backoff::Error::transient(err),
+    }
+}
+
+/// Subscribes to `rx` and POSTs every change record to `url` as JSON,
+/// retrying transient failures with exponential backoff and giving up
+/// immediately on a 4xx response.
+pub async fn run(url: String, mut rx: broadcast::Receiver<ChangeRecord>) {
+    let client = reqwest::Client::new();
+    loop {
+        let record = match rx.recv().await {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                eprintln!("webhook notifier dropped {skipped} change records, falling behind");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let result = backoff::future::retry(default_backoff(), || {
+            let client = client.clone();
+            let record = record.clone();
+            let url = url.clone();
+            async move {
+                let resp = client
+                    .post(&url)
+                    .json(&record)
+                    .send()
+                    .await
+                    .map_err(backoff::Error::transient)?;
+                resp.error_for_status().map_err(classify_error)
+            }
+        })
+        .await;
+
+        if let Err(e) = result {
+            eprintln!("webhook delivery to {url} failed permanently: {e}");
+        }
+    }
+}